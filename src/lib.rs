@@ -6,13 +6,167 @@ use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use aes::Aes256;
 use aes::cipher::KeyIvInit;
-use anyhow::{Context, Result};
+use argon2::Argon2;
 use cfb8::cipher::AsyncStreamCipher;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use walkdir::WalkDir;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Bedrock resource pack magic, written at offset 0x4 of `contents.json`.
+const CONTENTS_MAGIC: u32 = 0x9BCFB9FC;
+/// Version dword written at offset 0x0 of `contents.json`.
+const CONTENTS_VERSION: u32 = 3;
+/// The encrypted content list always starts at this offset.
+const CONTENTS_HEADER_LEN: usize = 0x100;
+
+/// Files that are never treated as encryptable pack content.
+const NON_CONTENT_FILES: &[&str] = &["manifest.json", "pack_icon.png", "contents.json"];
+
+/// Errors that can occur while decrypting or encrypting a resource pack.
+/// This is the error type that crosses the FFI boundary: callers get back
+/// a status code (see [`RpDecryptError::code`]) plus a human-readable
+/// message, instead of a bare `bool` or an aborting `unwrap`.
+#[derive(Debug)]
+enum RpDecryptError {
+    KeyWrongLength { expected: usize, found: usize },
+    InvalidUtf8,
+    MissingManifest,
+    DecryptFailed(String),
+    Io(String),
+}
+
+impl std::fmt::Display for RpDecryptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpDecryptError::KeyWrongLength { expected, found } =>
+                write!(f, "key must be {} bytes, found {}", expected, found),
+            RpDecryptError::InvalidUtf8 => write!(f, "argument was not valid UTF-8"),
+            RpDecryptError::MissingManifest => write!(f, "pack is missing manifest.json"),
+            RpDecryptError::DecryptFailed(reason) => write!(f, "decryption failed: {}", reason),
+            RpDecryptError::Io(reason) => write!(f, "I/O error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RpDecryptError {}
+
+impl RpDecryptError {
+    /// The status code written back across the FFI boundary. `0` is
+    /// reserved for success and is never returned here.
+    fn code(&self) -> i32 {
+        match self {
+            RpDecryptError::KeyWrongLength { .. } => 1,
+            RpDecryptError::InvalidUtf8 => 2,
+            RpDecryptError::MissingManifest => 3,
+            RpDecryptError::DecryptFailed(_) => 4,
+            RpDecryptError::Io(_) => 5,
+        }
+    }
+}
+
+impl From<std::io::Error> for RpDecryptError {
+    fn from(error: std::io::Error) -> Self {
+        RpDecryptError::Io(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for RpDecryptError {
+    fn from(error: serde_json::Error) -> Self {
+        RpDecryptError::DecryptFailed(format!("invalid JSON: {}", error))
+    }
+}
+
+/// Validates that `key` is exactly `expected` bytes, as required by AES-256.
+fn check_key_len(key: &[u8], expected: usize) -> Result<(), RpDecryptError> {
+    if key.len() != expected {
+        return Err(RpDecryptError::KeyWrongLength { expected, found: key.len() });
+    }
+    Ok(())
+}
 
 /// Utility method to open a file from a path.
-fn open_with_context(path: &PathBuf) -> Result<File> {
-    File::open(path).with_context(|| format!("Unable to open '{:?}'", path))
+fn open_with_context(path: &PathBuf) -> Result<File, RpDecryptError> {
+    File::open(path).map_err(|error| {
+        RpDecryptError::Io(format!("Unable to open '{:?}': {}", path, error))
+    })
+}
+
+/// Generates a random 32-byte (ASCII) AES-256 key, matching the format
+/// the rest of the crate expects from `key.as_bytes()`.
+fn random_key() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+/// Derives a `dklen`-byte AES key from a passphrase using the given KDF
+/// (PBKDF2-HMAC-SHA256 or Argon2id), then reinterprets the raw bytes as a `String` so it can flow through
+/// the same `key.as_bytes()` path as every other key in this crate.
+///
+/// SAFETY: the resulting `String` is only ever read back via `as_bytes`,
+/// which hands back the buffer verbatim regardless of UTF-8 validity.
+fn derive_key(passphrase: &str, kdf: &Kdf) -> Result<String, RpDecryptError> {
+    let key_bytes = match kdf {
+        Kdf::Pbkdf2(params) => derive_key_pbkdf2(passphrase, params)?,
+        Kdf::Argon2(params) => derive_key_argon2(passphrase, params)?,
+    };
+    Ok(unsafe { String::from_utf8_unchecked(key_bytes) })
+}
+
+/// PBKDF2-HMAC-SHA256, following the parameters openethereum stores in
+/// its `KdfPbkdf2Params` (`dkLen`, `salt`, `c`, `prf`).
+fn derive_key_pbkdf2(passphrase: &str, params: &KdfPbkdf2Params) -> Result<Vec<u8>, RpDecryptError> {
+    let salt = hex::decode(&params.salt)
+        .map_err(|error| RpDecryptError::DecryptFailed(format!("invalid PBKDF2 salt, expected hex: {}", error)))?;
+    let mut derived_key = vec![0u8; params.dklen as usize];
+
+    let block_count = (params.dklen as usize + 31) / 32;
+    for block_index in 1..=block_count {
+        let mut u = HmacSha256::new_from_slice(passphrase.as_bytes())
+            .map_err(|error| RpDecryptError::DecryptFailed(format!("PBKDF2 HMAC setup failed: {}", error)))?;
+        u.update(&salt);
+        u.update(&(block_index as u32).to_be_bytes());
+        let mut u = u.finalize().into_bytes();
+        let mut t = u;
+
+        for _ in 1..params.c {
+            let mut mac = HmacSha256::new_from_slice(passphrase.as_bytes())
+                .map_err(|error| RpDecryptError::DecryptFailed(format!("PBKDF2 HMAC setup failed: {}", error)))?;
+            mac.update(&u);
+            u = mac.finalize().into_bytes();
+            for (t_byte, u_byte) in t.iter_mut().zip(u.iter()) {
+                *t_byte ^= u_byte;
+            }
+        }
+
+        let start = (block_index - 1) * 32;
+        let end = std::cmp::min(start + 32, params.dklen as usize);
+        derived_key[start..end].copy_from_slice(&t[..end - start]);
+    }
+
+    Ok(derived_key)
+}
+
+/// Argon2 key derivation, as used by passdom's keygen.
+fn derive_key_argon2(passphrase: &str, params: &KdfArgon2Params) -> Result<Vec<u8>, RpDecryptError> {
+    let salt = hex::decode(&params.salt)
+        .map_err(|error| RpDecryptError::DecryptFailed(format!("invalid Argon2 salt, expected hex: {}", error)))?;
+    let argon2_params = argon2::Params::new(params.m_cost, params.t_cost, params.p_cost, Some(params.dklen as usize))
+        .map_err(|error| RpDecryptError::DecryptFailed(format!("invalid Argon2 parameters: {}", error)))?;
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, argon2_params);
+
+    let mut derived_key = vec![0u8; params.dklen as usize];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut derived_key)
+        .map_err(|error| RpDecryptError::DecryptFailed(format!("Argon2 key derivation failed: {}", error)))?;
+    Ok(derived_key)
 }
 
 /// Decrypts the resource pack contents in a directory.
@@ -21,7 +175,9 @@ fn internal_decrypt(
     key: String, // A string of the key, 32 bits.
     pack_dir: String, // Path to the pack directory.
     output_dir: String // Path to the output directory.
-) -> Result<bool> {
+) -> Result<bool, RpDecryptError> {
+    check_key_len(key.as_bytes(), 32)?;
+
     let input_path = Path::new(&pack_dir);
     let output_path = Path::new(&output_dir);
 
@@ -30,7 +186,13 @@ fn internal_decrypt(
 
     // Copy 'manifest.json' and 'pack_icon.png'.
     for file in &["manifest.json", "pack_icon.png"] {
-        copy(input_path.join(file), output_path.join(file))?;
+        copy(input_path.join(file), output_path.join(file)).map_err(|error| {
+            if *file == "manifest.json" && error.kind() == std::io::ErrorKind::NotFound {
+                RpDecryptError::MissingManifest
+            } else {
+                RpDecryptError::Io(format!("Unable to copy '{}': {}", file, error))
+            }
+        })?;
     }
 
     let content = {
@@ -41,7 +203,7 @@ fn internal_decrypt(
         file.seek(SeekFrom::Start(0x100))?;
         file.read_to_end(&mut buffer)?; // encrypted content list
         Aes256Cfb8Dec::new_from_slices(&key_bytes, &key_bytes[0..16])
-            .unwrap()
+            .map_err(|error| RpDecryptError::DecryptFailed(format!("invalid master key/IV: {}", error)))?
             .decrypt(&mut buffer);
         serde_json::from_slice::<Content>(&buffer)?
     };
@@ -83,12 +245,13 @@ fn internal_decrypt(
             }
             Some(key) => {
                 let key_bytes = key.as_bytes();
+                check_key_len(key_bytes, 32)?;
 
                 let mut file = open_with_context(&input_entry_path)?;
                 let mut buffer = Vec::new();
                 file.read_to_end(&mut buffer)?;
                 Aes256Cfb8Dec::new_from_slices(key_bytes, &key_bytes[0..16])
-                    .unwrap()
+                    .map_err(|error| RpDecryptError::DecryptFailed(format!("invalid content key/IV: {}", error)))?
                     .decrypt(&mut buffer);
                 if content_entry.path.ends_with(".json") {
                     // validate and prettify json
@@ -113,8 +276,169 @@ fn internal_decrypt(
     Ok(true)
 }
 
+/// Decrypts every resource pack found under `root_dir` into a mirrored
+/// directory tree under `output_dir`, looking up each pack's master key in
+/// a JSON keystore by its `manifest.json` `header.uuid`.
+/// Packs whose UUID has no keystore entry are skipped, not decrypted; the
+/// first pack that fails to decrypt stops the batch with its error.
+/// Returns true once every pack with a keystore entry was decrypted.
+fn internal_decrypt_batch(
+    keystore_path: String, // Path to the JSON keystore file.
+    root_dir: String, // Path to a directory containing one or more packs.
+    output_dir: String // Path to the output directory.
+) -> Result<bool, RpDecryptError> {
+    let root_path = Path::new(&root_dir);
+    let output_path = Path::new(&output_dir);
+
+    let keystore: Vec<KeystoreEntry> = {
+        let file = open_with_context(&PathBuf::from(&keystore_path))?;
+        serde_json::from_reader(file)?
+    };
+
+    for entry in WalkDir::new(root_path).into_iter().filter_map(|entry| entry.ok()) {
+        if entry.file_name() != "manifest.json" {
+            continue;
+        }
+
+        let pack_dir = entry.path().parent().unwrap();
+        let manifest: Manifest = serde_json::from_reader(open_with_context(&pack_dir.join("manifest.json"))?)?;
+
+        let keystore_entry = keystore.iter().find(|entry| entry.uuid == manifest.header.uuid);
+        let key = match keystore_entry {
+            Some(entry) => resolve_key(entry)?,
+            None => {
+                println!("No keystore entry for '{}', skipping", manifest.header.uuid);
+                continue;
+            }
+        };
+
+        let relative_path = pack_dir.strip_prefix(root_path).map_err(|error| {
+            RpDecryptError::Io(format!("Unable to relativize '{:?}': {}", pack_dir, error))
+        })?;
+        let output_pack_dir = output_path.join(relative_path);
+
+        internal_decrypt(
+            key,
+            pack_dir.to_string_lossy().to_string(),
+            output_pack_dir.to_string_lossy().to_string())?;
+    }
+
+    Ok(true)
+}
+
+/// Encrypts a plaintext resource pack directory back into the on-disk,
+/// Bedrock-encrypted form. This is the inverse of [`internal_decrypt`].
+/// Returns true if the resource pack was encrypted successfully.
+fn internal_encrypt(
+    key: String, // A string of the master key, 32 bytes.
+    pack_dir: String, // Path to the plaintext pack directory.
+    output_dir: String // Path to the output directory.
+) -> Result<bool, RpDecryptError> {
+    check_key_len(key.as_bytes(), 32)?;
+
+    let input_path = Path::new(&pack_dir);
+    let output_path = Path::new(&output_dir);
+
+    // Create the output path.
+    create_dir_all(output_path)?;
+
+    // Copy 'manifest.json' and 'pack_icon.png'.
+    for file in &["manifest.json", "pack_icon.png"] {
+        copy(input_path.join(file), output_path.join(file)).map_err(|error| {
+            if *file == "manifest.json" && error.kind() == std::io::ErrorKind::NotFound {
+                RpDecryptError::MissingManifest
+            } else {
+                RpDecryptError::Io(format!("Unable to copy '{}': {}", file, error))
+            }
+        })?;
+    }
+
+    // Walk the pack directory and encrypt every content file with its own
+    // randomly generated key.
+    let mut content_entries = Vec::new();
+    for entry in WalkDir::new(input_path).into_iter().filter_map(|entry| entry.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let input_entry_path = entry.path().to_path_buf();
+        let relative_path = input_entry_path.strip_prefix(input_path).map_err(|error| {
+            RpDecryptError::Io(format!("Unable to relativize '{:?}': {}", input_entry_path, error))
+        })?;
+        let relative_path_str = relative_path.to_string_lossy().replace('\\', "/");
+
+        if NON_CONTENT_FILES.contains(&relative_path_str.as_str()) {
+            continue;
+        }
+
+        let output_entry_path = output_path.join(relative_path);
+        create_dir_all(output_entry_path.parent().unwrap())?;
+
+        let entry_key = random_key();
+        let entry_key_bytes = entry_key.as_bytes();
+
+        let mut file = open_with_context(&input_entry_path)?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer)?;
+        Aes256Cfb8Enc::new_from_slices(entry_key_bytes, &entry_key_bytes[0..16])
+            .map_err(|error| RpDecryptError::DecryptFailed(format!("invalid content key/IV: {}", error)))?
+            .encrypt(&mut buffer);
+        File::create(output_entry_path)?.write_all(&buffer)?;
+
+        content_entries.push(ContentEntry {
+            path: relative_path_str,
+            key: Some(entry_key),
+        });
+    }
+
+    // Encrypt the content list with the master key.
+    let mut buffer = serde_json::to_vec(&Content { content: content_entries })?;
+    let key_bytes = key.as_bytes();
+    Aes256Cfb8Enc::new_from_slices(&key_bytes, &key_bytes[0..16])
+        .map_err(|error| RpDecryptError::DecryptFailed(format!("invalid master key/IV: {}", error)))?
+        .encrypt(&mut buffer);
+
+    // Reconstruct the fixed header and append the encrypted content list.
+    let mut contents_file = File::create(output_path.join("contents.json"))?;
+    let mut header = vec![0u8; CONTENTS_HEADER_LEN];
+    header[0..4].copy_from_slice(&CONTENTS_VERSION.to_le_bytes());
+    header[4..8].copy_from_slice(&CONTENTS_MAGIC.to_le_bytes());
+    contents_file.write_all(&header)?;
+    contents_file.write_all(&buffer)?;
+
+    Ok(true)
+}
+
+/// Reads a `*const u8`/len pair passed in from the FFI boundary as a UTF-8
+/// string, without panicking on invalid input.
+unsafe fn read_str(ptr: *const u8, len: i32) -> Result<String, RpDecryptError> {
+    let buffer = std::slice::from_raw_parts(ptr, len as usize);
+    std::str::from_utf8(buffer)
+        .map(str::to_string)
+        .map_err(|_| RpDecryptError::InvalidUtf8)
+}
+
+/// Writes `error`'s message into the caller-provided out-buffer, truncating
+/// to fit, and returns `error`'s status code.
+fn write_error(error: RpDecryptError, error_out: *mut u8, error_out_len: i32) -> i32 {
+    let code = error.code();
+    let message = error.to_string();
+    let message_bytes = message.as_bytes();
+    let capacity = error_out_len.max(0) as usize;
+    let write_len = message_bytes.len().min(capacity);
+
+    if write_len > 0 {
+        unsafe {
+            std::ptr::copy_nonoverlapping(message_bytes.as_ptr(), error_out, write_len);
+        }
+    }
+
+    code
+}
+
 /// Decrypts the resource pack contents in a directory.
-/// Returns true if the resource pack was decrypted successfully.
+/// Returns `0` on success, or a non-zero [`RpDecryptError::code`] on
+/// failure with a human-readable message written into `error_out`.
 #[no_mangle]
 pub extern fn decrypt(
     key: *const u8, // A string of the key, 32 bits.
@@ -122,30 +446,78 @@ pub extern fn decrypt(
     pack_dir: *const u8, // Path to the pack directory.
     pack_dir_len: i32, // Length of the pack directory.
     output_dir: *const u8, // Path to the output directory.
-    output_dir_len: i32 // Length of the output directory.
-) -> bool {
-    // Convert to strings.
-    let key_buffer = unsafe { std::slice::from_raw_parts(key, key_len as usize) };
-    let key_str = std::str::from_utf8(key_buffer).unwrap();
+    output_dir_len: i32, // Length of the output directory.
+    error_out: *mut u8, // Caller-provided buffer to receive an error message.
+    error_out_len: i32 // Length of `error_out`.
+) -> i32 {
+    let result = (|| -> Result<bool, RpDecryptError> {
+        let key_str = unsafe { read_str(key, key_len) }?;
+        let pack_dir_str = unsafe { read_str(pack_dir, pack_dir_len) }?;
+        let output_dir_str = unsafe { read_str(output_dir, output_dir_len) }?;
 
-    let pack_dir_buffer = unsafe { std::slice::from_raw_parts(pack_dir, pack_dir_len as usize) };
-    let pack_dir_str = std::str::from_utf8(pack_dir_buffer).unwrap();
+        internal_decrypt(key_str, pack_dir_str, output_dir_str)
+    })();
 
-    let output_dir_buffer = unsafe { std::slice::from_raw_parts(output_dir, output_dir_len as usize) };
-    let output_dir_str = std::str::from_utf8(output_dir_buffer).unwrap();
+    match result {
+        Ok(_) => 0,
+        Err(error) => write_error(error, error_out, error_out_len),
+    }
+}
 
-    // Attempt to decrypt the resource pack.
-    let result = internal_decrypt(
-        key_str.to_string(),
-        pack_dir_str.to_string(),
-        output_dir_str.to_string());
+/// Decrypts every resource pack found under a root directory, keyed by a
+/// JSON keystore mapping pack UUID to master key.
+/// Returns `0` on success, or a non-zero [`RpDecryptError::code`] on
+/// failure with a human-readable message written into `error_out`.
+#[no_mangle]
+pub extern fn decrypt_batch(
+    keystore_path: *const u8, // Path to the JSON keystore file.
+    keystore_path_len: i32, // Length of the keystore path.
+    root_dir: *const u8, // Path to a directory containing one or more packs.
+    root_dir_len: i32, // Length of the root directory.
+    output_dir: *const u8, // Path to the output directory.
+    output_dir_len: i32, // Length of the output directory.
+    error_out: *mut u8, // Caller-provided buffer to receive an error message.
+    error_out_len: i32 // Length of `error_out`.
+) -> i32 {
+    let result = (|| -> Result<bool, RpDecryptError> {
+        let keystore_path_str = unsafe { read_str(keystore_path, keystore_path_len) }?;
+        let root_dir_str = unsafe { read_str(root_dir, root_dir_len) }?;
+        let output_dir_str = unsafe { read_str(output_dir, output_dir_len) }?;
+
+        internal_decrypt_batch(keystore_path_str, root_dir_str, output_dir_str)
+    })();
 
     match result {
-        Ok(_) => true,
-        Err(_error) => {
-            println!("Error: {:?}", _error);
-            false
-        }
+        Ok(_) => 0,
+        Err(error) => write_error(error, error_out, error_out_len),
+    }
+}
+
+/// Encrypts the resource pack contents in a directory.
+/// Returns `0` on success, or a non-zero [`RpDecryptError::code`] on
+/// failure with a human-readable message written into `error_out`.
+#[no_mangle]
+pub extern fn encrypt(
+    key: *const u8, // A string of the key, 32 bytes.
+    key_len: i32, // Length of the key.
+    pack_dir: *const u8, // Path to the pack directory.
+    pack_dir_len: i32, // Length of the pack directory.
+    output_dir: *const u8, // Path to the output directory.
+    output_dir_len: i32, // Length of the output directory.
+    error_out: *mut u8, // Caller-provided buffer to receive an error message.
+    error_out_len: i32 // Length of `error_out`.
+) -> i32 {
+    let result = (|| -> Result<bool, RpDecryptError> {
+        let key_str = unsafe { read_str(key, key_len) }?;
+        let pack_dir_str = unsafe { read_str(pack_dir, pack_dir_len) }?;
+        let output_dir_str = unsafe { read_str(output_dir, output_dir_len) }?;
+
+        internal_encrypt(key_str, pack_dir_str, output_dir_str)
+    })();
+
+    match result {
+        Ok(_) => 0,
+        Err(error) => write_error(error, error_out, error_out_len),
     }
 }
 
@@ -159,6 +531,59 @@ struct ManifestHeader {
     uuid: String,
 }
 
+/// A single entry in a keystore file, mapping a pack UUID to its master key.
+/// When `kdf` is present, `key` holds a passphrase instead of a raw key,
+/// and the real AES key is derived from it on demand via [`resolve_key`].
+#[derive(Serialize, Deserialize, Debug)]
+struct KeystoreEntry {
+    uuid: String,
+    key: String,
+    #[serde(default)]
+    kdf: Option<Kdf>,
+}
+
+/// Resolves a keystore entry to its 32-byte master key, deriving it from
+/// the stored passphrase if the entry carries KDF parameters.
+fn resolve_key(entry: &KeystoreEntry) -> Result<String, RpDecryptError> {
+    match &entry.kdf {
+        Some(kdf) => derive_key(&entry.key, kdf),
+        None => Ok(entry.key.clone()),
+    }
+}
+
+/// Key-derivation parameters for a passphrase-backed keystore entry.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "kdf", rename_all = "lowercase")]
+enum Kdf {
+    Pbkdf2(KdfPbkdf2Params),
+    Argon2(KdfArgon2Params),
+}
+
+/// Mirrors openethereum's `KdfPbkdf2Params`.
+#[derive(Serialize, Deserialize, Debug)]
+struct KdfPbkdf2Params {
+    dklen: u32,
+    salt: String, // hex-encoded
+    c: u32,
+    prf: Prf,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+enum Prf {
+    HmacSha256,
+}
+
+/// Argon2 parameters, as used by passdom's keygen.
+#[derive(Serialize, Deserialize, Debug)]
+struct KdfArgon2Params {
+    dklen: u32,
+    salt: String, // hex-encoded
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct Content {
     // version: u32,
@@ -171,4 +596,5 @@ struct ContentEntry {
     key: Option<String>,
 }
 
-type Aes256Cfb8Dec = cfb8::Decryptor<Aes256>;
\ No newline at end of file
+type Aes256Cfb8Dec = cfb8::Decryptor<Aes256>;
+type Aes256Cfb8Enc = cfb8::Encryptor<Aes256>;
\ No newline at end of file